@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::source::SourceConfig;
+use crate::trigger::{Mode, Slope};
+
+const DEFAULT_HISTORY_SIZE: usize = 1_000;
+const DEFAULT_VOLTAGE_REFERENCE: f32 = 5.0;
+
+// ReadHistory::new sizes its ring buffer directly from this, and indexes
+// into it mod history_size, so anything below 1 would panic on the very
+// first sample
+const MIN_HISTORY_SIZE: usize = 1;
+
+/// Scope configuration persisted across sessions: the voltage reference
+/// (volts the `[0, 1]` sample domain maps to), the history window length,
+/// the selected data source, and the trigger settings. Loaded once at
+/// startup and rewritten whenever a setting changes via `set_config`.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct Config {
+    pub voltage_reference: f32,
+    pub history_size: usize,
+    pub source: SourceConfig,
+    pub trigger_threshold: f32,
+    pub trigger_slope: Slope,
+    pub trigger_mode: Mode,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            voltage_reference: DEFAULT_VOLTAGE_REFERENCE,
+            history_size: DEFAULT_HISTORY_SIZE,
+            source: SourceConfig::Simulated,
+            trigger_threshold: 0.5,
+            trigger_slope: Slope::Rising,
+            trigger_mode: Mode::Auto,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the persisted config from the OS config directory, falling
+    /// back to defaults if it's missing or fails to parse.
+    pub fn load() -> Self {
+        let mut config: Config = Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        config.clamp_history_size();
+        config
+    }
+
+    /// Clamps `history_size` to a value `ReadHistory` can safely be built
+    /// from. Called on every load and before every `set_config` write, since
+    /// a `0` sneaking through would panic the first time it's used to index
+    /// the ring buffer.
+    pub(crate) fn clamp_history_size(&mut self) {
+        self.history_size = self.history_size.max(MIN_HISTORY_SIZE);
+    }
+
+    /// Persists this config to the OS config directory, creating it if
+    /// necessary.
+    pub fn save(&self) {
+        let Some(path) = Self::path() else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(contents) = serde_json::to_string_pretty(self) {
+            let _ = fs::write(path, contents);
+        }
+    }
+
+    fn path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("tiny-oscope").join("config.json"))
+    }
+}
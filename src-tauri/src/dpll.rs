@@ -0,0 +1,145 @@
+const PHASE_BITS: u32 = 32;
+const PHASE_MASK: i64 = 0xffff_ffff;
+const MID_PHASE: i64 = 0x8000_0000;
+
+// `pe` is at most `MID_PHASE` in magnitude, so this keeps `pe * gain` safely
+// clear of i64::MAX with room to spare for the integrator/ftw additions that
+// follow it in `tick` -- a generous ceiling given the defaults below are
+// 1 << 20 and 1 << 24.
+const MAX_GAIN: i64 = 1 << 30;
+
+// placeholder sample rate used only until the first `tick` reports a real
+// one (derived from inter-sample timing, which isn't known at construction)
+const NOMINAL_SAMPLE_RATE: f32 = 1_000.0;
+
+/// A digital phase-locked loop that locks onto a signal's threshold
+/// crossings and continuously tracks its frequency and phase, rather than
+/// re-measuring peak-to-peak timing on every wave.
+pub struct Dpll {
+    sample_rate: f32,
+    threshold: f32,
+    last_sample: f32,
+
+    phase: i64,
+    ftw: i64,
+    integrator: i64,
+    ftw_min: i64,
+    ftw_max: i64,
+    ki: i64,
+    kp: i64,
+}
+
+impl Dpll {
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            sample_rate: NOMINAL_SAMPLE_RATE,
+            threshold,
+            last_sample: threshold,
+            // starts at the PI loop's lock target (mid_phase) rather than 0,
+            // so a correctly-guessed initial ftw doesn't immediately read as
+            // a huge phase error on the very first edge
+            phase: MID_PHASE,
+            ftw: Self::frequency_to_ftw(1.0, NOMINAL_SAMPLE_RATE),
+            // seeded from the same initial guess as `ftw` rather than 0, since
+            // 0 sits well below `ftw_min` and would clamp the loop to its
+            // slowest trackable frequency on the very first correction
+            integrator: Self::frequency_to_ftw(1.0, NOMINAL_SAMPLE_RATE),
+            ftw_min: Self::frequency_to_ftw(0.1, NOMINAL_SAMPLE_RATE),
+            ftw_max: Self::frequency_to_ftw(NOMINAL_SAMPLE_RATE / 2.0, NOMINAL_SAMPLE_RATE),
+            ki: 1 << 20,
+            kp: 1 << 24,
+        }
+    }
+
+    fn frequency_to_ftw(frequency: f32, sample_rate: f32) -> i64 {
+        (frequency as f64 * (1i64 << PHASE_BITS) as f64 / sample_rate as f64) as i64
+    }
+
+    /// Sets the PI loop gains (integral, then proportional), clamped to
+    /// `±MAX_GAIN` so `pe * gain` in `tick` can't overflow `i64` regardless
+    /// of what a caller (e.g. the frontend) passes in.
+    pub fn set_gains(&mut self, ki: i64, kp: i64) {
+        self.ki = ki.clamp(-MAX_GAIN, MAX_GAIN);
+        self.kp = kp.clamp(-MAX_GAIN, MAX_GAIN);
+    }
+
+    /// The frequency the loop is currently locked to, in Hz.
+    pub fn frequency(&self) -> f32 {
+        (self.ftw as f64 * self.sample_rate as f64 / (1i64 << PHASE_BITS) as f64) as f32
+    }
+
+    /// The current phase, normalized to `[0, 1)`.
+    pub fn phase(&self) -> f32 {
+        self.phase as f32 / (1i64 << PHASE_BITS) as f32
+    }
+
+    /// Advances the loop by one sample tick, feeding in the latest reading
+    /// and the effective sample rate it arrived at.
+    pub fn tick(&mut self, sample: f32, sample_rate: f32) {
+        // the tracking range is a fraction of the sample rate, so it has to
+        // be re-derived as the measured rate drifts rather than fixed at
+        // construction time against some nominal rate; clamp the existing
+        // ftw/integrator into the new bounds in case the range shrank
+        if sample_rate > 0.0 && sample_rate != self.sample_rate {
+            self.ftw_min = Self::frequency_to_ftw(0.1, sample_rate);
+            self.ftw_max = Self::frequency_to_ftw(sample_rate / 2.0, sample_rate);
+            self.ftw = self.ftw.clamp(self.ftw_min, self.ftw_max);
+            self.integrator = self.integrator.clamp(self.ftw_min, self.ftw_max);
+        }
+        self.sample_rate = sample_rate;
+        self.phase = (self.phase + self.ftw) & PHASE_MASK;
+
+        let rising_edge = self.last_sample < self.threshold && sample >= self.threshold;
+        self.last_sample = sample;
+
+        if !rising_edge {
+            return;
+        }
+
+        // the edge should ideally land at mid-phase; anything else is
+        // phase error the PI controller feeds back into the tuning word
+        let pe = MID_PHASE - self.phase;
+        self.integrator =
+            (self.integrator + ((pe * self.ki) >> PHASE_BITS)).clamp(self.ftw_min, self.ftw_max);
+        self.ftw =
+            (self.integrator + ((pe * self.kp) >> PHASE_BITS)).clamp(self.ftw_min, self.ftw_max);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locks_onto_a_sine_edge_train() {
+        let sample_rate = NOMINAL_SAMPLE_RATE;
+        let true_frequency = 1.0;
+
+        let mut dpll = Dpll::new(0.5);
+        for i in 0..3_000 {
+            let t = i as f32 / sample_rate;
+            let sample = ((2.0 * std::f32::consts::PI * true_frequency * t).sin() + 1.0) / 2.0;
+            dpll.tick(sample, sample_rate);
+        }
+
+        let locked = dpll.frequency();
+        assert!(
+            (locked - true_frequency).abs() < 0.1,
+            "expected to lock near {true_frequency} Hz, got {locked} Hz"
+        );
+    }
+
+    #[test]
+    fn set_gains_clamps_out_of_range_values() {
+        let mut dpll = Dpll::new(0.5);
+        dpll.set_gains(i64::MAX, i64::MIN);
+        assert_eq!(dpll.ki, MAX_GAIN);
+        assert_eq!(dpll.kp, -MAX_GAIN);
+
+        // a rising edge (low tick, then high tick) with maximal gains and a
+        // phase error should still run without overflowing, rather than just
+        // asserting the stored fields
+        dpll.tick(0.0, NOMINAL_SAMPLE_RATE);
+        dpll.tick(1.0, NOMINAL_SAMPLE_RATE);
+    }
+}
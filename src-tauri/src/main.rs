@@ -3,128 +3,310 @@
     windows_subsystem = "windows"
 )]
 
-use std::ops::{RangeInclusive, Rem};
-use std::sync::Mutex;
-use std::time::{Duration, Instant};
+mod config;
+mod dpll;
+mod quantile;
+mod source;
+mod spectrum;
+mod trigger;
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use tauri::State;
 
-// values are read in as f32 in the closed range [0, 1],
-// where 0 represents 0V and 1 represents MAX_VOLT V
-const MAX_VOLT: f32 = 5.0;
+use config::Config;
+use dpll::Dpll;
+use quantile::P2Quantile;
+use source::{Acquisition, SourceConfig};
+use trigger::{Mode as TriggerMode, Slope as TriggerSlope, Trigger};
+
+// values are read in as f32 in the closed range [0, 1], where 0 represents
+// 0V and 1 represents the configured voltage reference
 
 fn main() {
+    let config = Config::load();
+
+    let history = Arc::new(ReadHistory::new(
+        config.history_size,
+        0.5,
+        config.voltage_reference,
+    ));
+    history.set_trigger(
+        config.trigger_threshold,
+        config.trigger_slope,
+        config.trigger_mode,
+    );
+    let acquisition = Acquisition::start(config.source.clone(), history.clone());
+
     tauri::Builder::default()
-        .manage(Mutex::new(Ticker::new()))
-        .manage(Mutex::new(ReadHistory::new(0.5)))
-        .invoke_handler(tauri::generate_handler![analog_read, stats])
+        .manage(history)
+        .manage(Mutex::new(Some(acquisition)))
+        .manage(Mutex::new(config))
+        .invoke_handler(tauri::generate_handler![
+            analog_read,
+            waveform_read,
+            stats,
+            spectrum,
+            set_dpll_gains,
+            set_source,
+            set_trigger,
+            arm_trigger,
+            get_config,
+            set_config
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
-struct Ticker(u8);
-
-impl Ticker {
-    fn new() -> Self {
-        Self(0)
-    }
+fn load_f32(slot: &AtomicU32) -> f32 {
+    f32::from_bits(slot.load(Ordering::Acquire))
+}
 
-    fn tick(&mut self) {
-        self.0 = self.0.wrapping_add(1);
-    }
+fn store_f32(slot: &AtomicU32, v: f32) {
+    slot.store(v.to_bits(), Ordering::Release);
+}
 
-    fn value(&self) -> u8 {
-        self.0
+/// Read-modify-write an `AtomicU32`-backed `f32` via a CAS loop.
+fn update_f32(slot: &AtomicU32, f: impl Fn(f32) -> f32) {
+    let mut current = slot.load(Ordering::Acquire);
+    loop {
+        let new = f(f32::from_bits(current)).to_bits();
+        match slot.compare_exchange_weak(current, new, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => return,
+            Err(actual) => current = actual,
+        }
     }
 }
 
-const HISTORY_SIZE: usize = 1_000;
+/// State that isn't a good fit for plain atomics (the DPLL's control loop,
+/// the P² quantile markers, the trigger, and the last-sample timestamp used
+/// to derive `sample_rate`). Guarded by a single short-lived lock taken once
+/// per `push`, so it never contends with the ring buffer's atomic writes —
+/// but it's still a mutex: `push` and every `Aux`-backed reader
+/// (`frequency`/`median`/`p95`/`p99`/`trigger_offset`) do serialize on it.
+struct Aux {
+    last_sample_at: Option<Instant>,
+    dpll: Dpll,
+    median: P2Quantile,
+    p95: P2Quantile,
+    p99: P2Quantile,
+    trigger: Trigger,
+}
 
-struct ReadHistory {
-    head: usize,
-    data: [f32; HISTORY_SIZE],
-    average: f32,
-    max: f32,
-    min: f32,
-    peaked_at: Option<Instant>,
-    frequency: f32,
-    wavelength: Option<Duration>,
+pub(crate) struct ReadHistory {
+    head: AtomicUsize,
+    data: Vec<AtomicU32>,
+    average: AtomicU32,
+    max: AtomicU32,
+    min: AtomicU32,
+    sample_rate: AtomicU32,
+    max_volt: AtomicU32,
+    // set once a `Mode::Single` trigger fires, halting `push` so the
+    // captured frame stops sliding out of the ring buffer; cleared by
+    // `arm_trigger`/`set_trigger`
+    halted: AtomicBool,
+    aux: Mutex<Aux>,
 }
 
 impl ReadHistory {
-    fn new(default_value: f32) -> Self {
+    pub(crate) fn new(history_size: usize, default_value: f32, max_volt: f32) -> Self {
         Self {
-            head: 0,
-            data: [default_value; HISTORY_SIZE],
-            average: default_value,
-            max: default_value,
-            min: default_value,
-            peaked_at: None,
-            frequency: 0.0,
-            wavelength: None,
+            head: AtomicUsize::new(0),
+            data: (0..history_size)
+                .map(|_| AtomicU32::new(default_value.to_bits()))
+                .collect(),
+            average: AtomicU32::new(default_value.to_bits()),
+            max: AtomicU32::new(default_value.to_bits()),
+            min: AtomicU32::new(default_value.to_bits()),
+            sample_rate: AtomicU32::new(0.0_f32.to_bits()),
+            max_volt: AtomicU32::new(max_volt.to_bits()),
+            halted: AtomicBool::new(false),
+            aux: Mutex::new(Aux {
+                last_sample_at: None,
+                dpll: Dpll::new(default_value),
+                median: P2Quantile::new(0.5),
+                p95: P2Quantile::new(0.95),
+                p99: P2Quantile::new(0.99),
+                trigger: Trigger::new(),
+            }),
         }
     }
 
-    fn push(&mut self, n: f32) {
-        let old = self.data[self.head];
-        self.data[self.head] = n;
-        self.average -= old / self.data.len() as f32;
-        self.average += n / self.data.len() as f32;
-        self.head = (self.head + 1).rem(self.data.len());
+    /// Pushes a sample onto the ring buffer. The slot write and the
+    /// average/min/max accumulators are plain atomic stores/CAS, lock-free
+    /// against `ordered`/`latest`/`amplitude`. The DPLL/quantile/trigger
+    /// update that follows still takes `aux`'s mutex, so it does serialize
+    /// against `frequency`/`median`/`p95`/`p99`/`trigger_offset` — see the
+    /// note on `Aux`.
+    pub(crate) fn push(&self, n: f32) {
+        if self.halted.load(Ordering::Acquire) {
+            return;
+        }
 
-        // instead of just tracking ticks between min/max,
-        // this should record time elapsed in order to determine Hz;
-        // additionally, this needs to track wavelength, or distance
-        // between one max to the next max (or min to min)
+        let len = self.data.len();
+        let count = self.head.fetch_add(1, Ordering::AcqRel);
+        let index = count % len;
+        let slot = &self.data[index];
+        let old = load_f32(slot);
+        store_f32(slot, n);
 
-        self.max = self.max.max(n);
-        self.min = self.min.min(n);
+        update_f32(&self.average, |avg| {
+            avg - old / len as f32 + n / len as f32
+        });
+        update_f32(&self.max, |max| max.max(n));
+        update_f32(&self.min, |min| min.min(n));
 
-        // ignore local max/min within 5% margin of error (chosen arbitrarily)
-        let amplitude = (self.max - self.min) / 2.0;
-        let err_margin = amplitude * 0.05;
+        let mut aux = self.aux.lock().unwrap();
 
-        if self.max - n > err_margin {
-            // we're outside of a peak
-            if self.peaked_at.is_none() {
-                // we've just left the peak
-                self.peaked_at = Some(Instant::now());
+        // samples don't arrive at a fixed rate (a real source jitters), so
+        // track an effective sample rate as an exponential moving average
+        // of the inter-sample interval instead of assuming uniform spacing
+        let now = Instant::now();
+        let sample_rate = load_f32(&self.sample_rate);
+        let sample_rate = match aux.last_sample_at {
+            Some(last) => {
+                let dt = now.duration_since(last).as_secs_f32();
+                if dt > 0.0 {
+                    let instantaneous_rate = 1.0 / dt;
+                    sample_rate + (instantaneous_rate - sample_rate) * 0.1
+                } else {
+                    sample_rate
+                }
             }
-        } else if let Some(peaked_at) = self.peaked_at.take() {
-            // we've just entered a peak, completing a wave
-            let wavelength = Instant::now().duration_since(peaked_at);
-            self.wavelength = Some(wavelength);
-            self.frequency = 1.0 / wavelength.as_secs_f32();
-            self.peaked_at = None;
+            None => sample_rate,
+        };
+        aux.last_sample_at = Some(now);
+        store_f32(&self.sample_rate, sample_rate);
+
+        aux.median.observe(n);
+        aux.p95.observe(n);
+        aux.p99.observe(n);
+        if aux.trigger.observe(n, index) {
+            self.halted.store(true, Ordering::Release);
         }
+
+        // locks onto the signal's crossings instead of re-measuring
+        // peak-to-peak timing on every wave, so frequency/phase stay
+        // stable even between peaks
+        aux.dpll.tick(n, sample_rate);
+    }
+
+    /// The voltage reference: volts the `[0, 1]` sample domain maps to.
+    fn max_volt(&self) -> f32 {
+        load_f32(&self.max_volt)
+    }
+
+    fn set_voltage_reference(&self, max_volt: f32) {
+        store_f32(&self.max_volt, max_volt);
     }
 
     /// The amplitude, in volts.
     fn amplitude(&self) -> f32 {
-        (self.max - self.min) / 2.0 * MAX_VOLT
+        (load_f32(&self.max) - load_f32(&self.min)) / 2.0 * self.max_volt()
     }
 
     /// The frequency, in Hz.
     fn frequency(&self) -> f32 {
-        self.frequency
+        self.aux.lock().unwrap().dpll.frequency()
     }
 
     /// The wavelength, in seconds.
     fn wavelength(&self) -> f32 {
-        self.wavelength.map(|d| d.as_secs_f32()).unwrap_or(0.0)
+        let frequency = self.frequency();
+        if frequency > 0.0 {
+            1.0 / frequency
+        } else {
+            0.0
+        }
+    }
+
+    /// The effective sample rate, in Hz, derived from inter-sample timing.
+    fn sample_rate(&self) -> f32 {
+        load_f32(&self.sample_rate)
+    }
+
+    /// The streaming median estimate, in volts.
+    fn median(&self) -> f32 {
+        self.aux.lock().unwrap().median.value() * self.max_volt()
+    }
+
+    /// The streaming 95th percentile estimate, in volts.
+    fn p95(&self) -> f32 {
+        self.aux.lock().unwrap().p95.value() * self.max_volt()
+    }
+
+    /// The streaming 99th percentile estimate, in volts.
+    fn p99(&self) -> f32 {
+        self.aux.lock().unwrap().p99.value() * self.max_volt()
+    }
+
+    /// The ring buffer's contents in chronological order (oldest first).
+    fn ordered(&self) -> Vec<f32> {
+        let len = self.data.len();
+        let head = self.head.load(Ordering::Acquire) % len;
+        (0..len)
+            .map(|i| load_f32(&self.data[(head + i) % len]))
+            .collect()
+    }
+
+    /// The most recently pushed sample.
+    fn latest(&self) -> f32 {
+        let len = self.data.len();
+        let count = self.head.load(Ordering::Acquire);
+        let last = (count + len - 1) % len;
+        load_f32(&self.data[last])
+    }
+
+    fn set_dpll_gains(&self, ki: i64, kp: i64) {
+        self.aux.lock().unwrap().dpll.set_gains(ki, kp);
+    }
+
+    /// The offset of the most recent trigger event within `ordered()`'s
+    /// chronological frame, if any. `Trigger::observe` is fed the physical
+    /// ring-buffer index (cheapest to compute at push time), so it has to be
+    /// re-based against the current `head` here to line up with `ordered()`.
+    fn trigger_offset(&self) -> Option<usize> {
+        let physical = self.aux.lock().unwrap().trigger.offset()?;
+        let len = self.data.len();
+        let head = self.head.load(Ordering::Acquire) % len;
+        Some((physical + len - head) % len)
+    }
+
+    fn set_trigger(&self, threshold: f32, slope: TriggerSlope, mode: TriggerMode) {
+        self.aux.lock().unwrap().trigger.set(threshold, slope, mode);
+        self.halted.store(false, Ordering::Release);
+    }
+
+    fn arm_trigger(&self) {
+        self.aux.lock().unwrap().trigger.arm();
+        self.halted.store(false, Ordering::Release);
     }
 }
 
 #[tauri::command]
-fn analog_read(hist: State<Mutex<ReadHistory>>, ticker: State<Mutex<Ticker>>) -> f32 {
-    let mut hist = hist.lock().unwrap();
-    let mut ticker = ticker.lock().unwrap();
-    ticker.tick();
-    let t = ((ticker.value() as f32 / 7.0).sin() + 1.0) / 2.0;
-    let v = lerp(0.2..=0.8, t);
+fn analog_read(hist: State<Arc<ReadHistory>>) -> f32 {
+    hist.latest()
+}
 
-    hist.push(v);
+/// The ring buffer's contents in chronological order, oldest first — the
+/// frame `trigger_offset` (from `stats`) is an index into.
+#[tauri::command]
+fn waveform_read(hist: State<Arc<ReadHistory>>) -> Vec<f32> {
+    hist.ordered()
+}
 
-    v
+#[tauri::command]
+fn set_source(
+    hist: State<Arc<ReadHistory>>,
+    acquisition: State<Mutex<Option<Acquisition>>>,
+    config: SourceConfig,
+) {
+    let mut acquisition = acquisition.lock().unwrap();
+    if let Some(prev) = acquisition.take() {
+        prev.stop();
+    }
+    *acquisition = Some(Acquisition::start(config, hist.inner().clone()));
 }
 
 #[derive(serde::Serialize)]
@@ -132,19 +314,135 @@ struct Stats {
     amplitude: f32,
     frequency: f32,
     wavelength: f32,
+    median: f32,
+    p95: f32,
+    p99: f32,
+    trigger_offset: Option<usize>,
 }
 
 #[tauri::command]
-fn stats(hist: State<Mutex<ReadHistory>>) -> Stats {
-    let hist = hist.lock().unwrap();
+fn stats(hist: State<Arc<ReadHistory>>) -> Stats {
     Stats {
         amplitude: hist.amplitude(),
         frequency: hist.frequency(),
         wavelength: hist.wavelength(),
+        median: hist.median(),
+        p95: hist.p95(),
+        p99: hist.p99(),
+        trigger_offset: hist.trigger_offset(),
+    }
+}
+
+#[tauri::command]
+fn set_trigger(
+    hist: State<Arc<ReadHistory>>,
+    threshold: f32,
+    slope: TriggerSlope,
+    mode: TriggerMode,
+) {
+    hist.set_trigger(threshold, slope, mode);
+}
+
+#[tauri::command]
+fn arm_trigger(hist: State<Arc<ReadHistory>>) {
+    hist.arm_trigger();
+}
+
+#[derive(serde::Serialize)]
+struct SpectrumResponse {
+    magnitudes: Vec<f32>,
+    dominant_frequency: f32,
+}
+
+#[tauri::command]
+fn spectrum(hist: State<Arc<ReadHistory>>) -> SpectrumResponse {
+    let spectrum::Spectrum {
+        magnitudes,
+        dominant_frequency,
+    } = spectrum::analyze(&hist.ordered(), hist.sample_rate());
+
+    SpectrumResponse {
+        magnitudes,
+        dominant_frequency,
+    }
+}
+
+#[tauri::command]
+fn set_dpll_gains(hist: State<Arc<ReadHistory>>, ki: i64, kp: i64) {
+    hist.set_dpll_gains(ki, kp);
+}
+
+#[tauri::command]
+fn get_config(config: State<Mutex<Config>>) -> Config {
+    config.lock().unwrap().clone()
+}
+
+#[tauri::command]
+fn set_config(
+    hist: State<Arc<ReadHistory>>,
+    acquisition: State<Mutex<Option<Acquisition>>>,
+    config: State<Mutex<Config>>,
+    mut new_config: Config,
+) {
+    new_config.clamp_history_size();
+
+    hist.set_voltage_reference(new_config.voltage_reference);
+    hist.set_trigger(
+        new_config.trigger_threshold,
+        new_config.trigger_slope,
+        new_config.trigger_mode,
+    );
+
+    let mut acquisition = acquisition.lock().unwrap();
+    if let Some(prev) = acquisition.take() {
+        prev.stop();
     }
+    *acquisition = Some(Acquisition::start(
+        new_config.source.clone(),
+        hist.inner().clone(),
+    ));
+    drop(acquisition);
+
+    // history_size only takes effect on next launch: the ring buffer is
+    // sized once in ReadHistory::new and isn't resized in place
+    new_config.save();
+    *config.lock().unwrap() = new_config;
 }
 
-#[inline]
-fn lerp(range: RangeInclusive<f32>, t: f32) -> f32 {
-    range.start() + (range.end() - range.start()) * t
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trigger::{Mode, Slope};
+
+    #[test]
+    fn ordered_reads_back_in_chronological_order_across_a_wrap() {
+        let hist = ReadHistory::new(4, 0.0, 1.0);
+        for n in [1.0, 2.0, 3.0, 4.0, 5.0, 6.0] {
+            hist.push(n);
+        }
+
+        // the buffer holds 4 slots; the oldest two samples (1.0, 2.0) have
+        // been overwritten, so the chronological order is 3, 4, 5, 6
+        assert_eq!(hist.ordered(), vec![3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(hist.latest(), 6.0);
+    }
+
+    #[test]
+    fn trigger_offset_lines_up_with_ordered_after_a_wrap() {
+        let hist = ReadHistory::new(4, 0.0, 1.0);
+        hist.set_trigger(0.5, Slope::Rising, Mode::Auto);
+
+        // pushes enough samples to wrap the 4-slot buffer at least once,
+        // with the last push landing a rising crossing
+        for n in [0.0, 0.0, 0.0, 0.0, 0.0, 1.0] {
+            hist.push(n);
+        }
+
+        let ordered = hist.ordered();
+        let offset = hist.trigger_offset().expect("trigger should have fired");
+        assert_eq!(
+            ordered[offset], 1.0,
+            "trigger_offset {offset} should index the crossing sample within ordered() {ordered:?}"
+        );
+    }
 }
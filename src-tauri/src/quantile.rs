@@ -0,0 +1,164 @@
+/// Streaming quantile estimator using the P² (piecewise-parabolic)
+/// algorithm: tracks a single quantile in O(1) memory without storing or
+/// sorting the observed samples.
+pub struct P2Quantile {
+    p: f64,
+    initial: Vec<f32>,
+    markers: Option<Markers>,
+}
+
+struct Markers {
+    // marker heights: running min, an interior estimate below the target
+    // quantile, the target quantile estimate, an interior estimate above it,
+    // running max
+    q: [f64; 5],
+    // actual marker positions
+    n: [i64; 5],
+    // desired marker positions
+    np: [f64; 5],
+    // desired position increments, applied once per observation
+    dn: [f64; 5],
+}
+
+impl P2Quantile {
+    pub fn new(p: f64) -> Self {
+        Self {
+            p,
+            initial: Vec::with_capacity(5),
+            markers: None,
+        }
+    }
+
+    pub fn observe(&mut self, x: f32) {
+        let Some(markers) = &mut self.markers else {
+            self.initial.push(x);
+            if self.initial.len() == 5 {
+                self.initial.sort_by(|a, b| a.total_cmp(b));
+                self.markers = Some(Markers::init(&self.initial, self.p));
+            }
+            return;
+        };
+
+        markers.observe(x);
+    }
+
+    /// The current quantile estimate.
+    pub fn value(&self) -> f32 {
+        match &self.markers {
+            Some(m) => m.q[2] as f32,
+            // not enough samples yet to seed the five markers
+            None => self.initial.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+impl Markers {
+    fn init(sorted: &[f32], p: f64) -> Self {
+        let q = [
+            sorted[0] as f64,
+            sorted[1] as f64,
+            sorted[2] as f64,
+            sorted[3] as f64,
+            sorted[4] as f64,
+        ];
+        Self {
+            q,
+            n: [1, 2, 3, 4, 5],
+            np: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            dn: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        }
+    }
+
+    fn observe(&mut self, x: f32) {
+        let x = x as f64;
+
+        let k = if x < self.q[0] {
+            self.q[0] = x;
+            0
+        } else if x >= self.q[4] {
+            self.q[4] = x;
+            3
+        } else {
+            (0..4)
+                .find(|&i| x >= self.q[i] && x < self.q[i + 1])
+                .unwrap_or(3)
+        };
+
+        for n in self.n.iter_mut().skip(k + 1) {
+            *n += 1;
+        }
+        for i in 0..5 {
+            self.np[i] += self.dn[i];
+        }
+
+        for i in 1..4 {
+            let d = self.np[i] - self.n[i] as f64;
+            let can_move_right = d >= 1.0 && self.n[i + 1] - self.n[i] > 1;
+            let can_move_left = d <= -1.0 && self.n[i - 1] - self.n[i] < -1;
+            if !can_move_right && !can_move_left {
+                continue;
+            }
+
+            let d = d.signum();
+            let parabolic = self.parabolic(i, d);
+            self.q[i] = if self.q[i - 1] < parabolic && parabolic < self.q[i + 1] {
+                parabolic
+            } else {
+                self.linear(i, d)
+            };
+            self.n[i] = (self.n[i] as f64 + d) as i64;
+        }
+    }
+
+    /// The P² parabolic prediction for marker `i`, nudged by `d` (`±1`).
+    fn parabolic(&self, i: usize, d: f64) -> f64 {
+        let (qm, q, qp) = (self.q[i - 1], self.q[i], self.q[i + 1]);
+        let (nm, n, np) = (
+            self.n[i - 1] as f64,
+            self.n[i] as f64,
+            self.n[i + 1] as f64,
+        );
+        q + d / (np - nm)
+            * ((n - nm + d) * (qp - q) / (np - n) + (np - n - d) * (q - qm) / (n - nm))
+    }
+
+    /// Linear interpolation fallback when the parabolic prediction would
+    /// leave the `(q[i-1], q[i+1])` bracket.
+    fn linear(&self, i: usize, d: f64) -> f64 {
+        let j = (i as f64 + d) as usize;
+        self.q[i] + d * (self.q[j] - self.q[i]) / (self.n[j] as f64 - self.n[i] as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // deterministic shuffle of `0..n` so observation order doesn't just
+    // hand the algorithm an already-sorted stream
+    fn shuffled(n: usize) -> Vec<f32> {
+        (0..n).map(|i| (i * 37 + 11) % n).map(|v| v as f32).collect()
+    }
+
+    #[test]
+    fn tracks_median_of_a_known_sample_set() {
+        let mut q = P2Quantile::new(0.5);
+        for x in shuffled(1_001) {
+            q.observe(x);
+        }
+
+        // median of 0..=1000 is 500
+        assert!((q.value() - 500.0).abs() < 10.0, "value was {}", q.value());
+    }
+
+    #[test]
+    fn tracks_p95_of_a_known_sample_set() {
+        let mut q = P2Quantile::new(0.95);
+        for x in shuffled(1_001) {
+            q.observe(x);
+        }
+
+        // the 95th percentile of 0..=1000 is 950
+        assert!((q.value() - 950.0).abs() < 20.0, "value was {}", q.value());
+    }
+}
@@ -0,0 +1,187 @@
+use std::io::Read;
+use std::ops::RangeInclusive;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::ReadHistory;
+
+/// Selects where samples come from.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+#[serde(tag = "type", content = "value")]
+pub enum SourceConfig {
+    /// The built-in simulated sine wave (the default).
+    Simulated,
+    /// Spawns `sh -c "<cmd>"` and reads raw bytes from its stdout, mapping
+    /// each byte `0..=255` to the `[0, 1]` range `ReadHistory::push` expects.
+    Command(String),
+    /// Reads framed samples from a serial port, one byte per sample.
+    Serial { path: String, baud_rate: u32 },
+}
+
+/// A running acquisition: a dedicated thread that reads from the
+/// configured source and pushes samples into `ReadHistory` as they
+/// arrive, decoupled from the frontend's `analog_read` poll rate.
+pub struct Acquisition {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Acquisition {
+    pub fn start(config: SourceConfig, hist: Arc<ReadHistory>) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+
+        let handle = thread::spawn(move || match config {
+            SourceConfig::Simulated => run_simulated(&hist, &thread_stop),
+            SourceConfig::Command(cmd) => run_command(&cmd, &hist, &thread_stop),
+            SourceConfig::Serial { path, baud_rate } => {
+                run_serial(&path, baud_rate, &hist, &thread_stop)
+            }
+        });
+
+        Self {
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Signals the acquisition thread to stop and waits for it to exit.
+    pub fn stop(mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn run_simulated(hist: &ReadHistory, stop: &AtomicBool) {
+    let mut t: u8 = 0;
+    while !stop.load(Ordering::Relaxed) {
+        t = t.wrapping_add(1);
+        let phase = ((t as f32 / 7.0).sin() + 1.0) / 2.0;
+        hist.push(lerp(0.2..=0.8, phase));
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn run_command(cmd: &str, hist: &ReadHistory, stop: &AtomicBool) {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(cmd).stdout(Stdio::piped());
+    #[cfg(unix)]
+    {
+        // its own process group, so `kill_tree` below can reach a grandchild
+        // `cmd` forks off rather than exec's into
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let Ok(mut child) = command.spawn() else {
+        return;
+    };
+
+    let Some(mut stdout) = child.stdout.take() else {
+        kill_tree(&mut child);
+        return;
+    };
+
+    // `stdout.read` below blocks with no timeout, so a stop request arriving
+    // while we're parked waiting on a quiet command has to be delivered by
+    // killing the child -- that's what unblocks the read (via EOF) instead
+    // of leaving `Acquisition::stop`'s `join` waiting on a thread that will
+    // never wake up on its own.
+    let done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            while !stop.load(Ordering::Relaxed) && !done.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(50));
+            }
+            kill_tree(&mut child);
+        });
+
+        let mut byte = [0u8; 1];
+        while !stop.load(Ordering::Relaxed) {
+            match stdout.read(&mut byte) {
+                Ok(0) => break, // EOF
+                Ok(_) => hist.push(byte[0] as f32 / 255.0),
+                Err(_) => break,
+            }
+        }
+        done.store(true, Ordering::Relaxed);
+    });
+}
+
+/// Kills `child` and anything it forked into its own process group (`sh -c`
+/// may fork a grandchild rather than exec into it, which would otherwise
+/// keep holding `stdout`'s write end open after `child` itself is gone).
+/// Falls back to just the immediate process on platforms without process
+/// groups.
+fn kill_tree(child: &mut std::process::Child) {
+    #[cfg(unix)]
+    {
+        extern "C" {
+            fn kill(pid: i32, sig: i32) -> i32;
+        }
+        const SIGKILL: i32 = 9;
+        unsafe {
+            kill(-(child.id() as i32), SIGKILL);
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = child.kill();
+    }
+}
+
+fn run_serial(path: &str, baud_rate: u32, hist: &ReadHistory, stop: &AtomicBool) {
+    let Ok(mut port) = serialport::new(path, baud_rate)
+        .timeout(Duration::from_millis(100))
+        .open()
+    else {
+        return;
+    };
+
+    let mut byte = [0u8; 1];
+    while !stop.load(Ordering::Relaxed) {
+        match port.read(&mut byte) {
+            Ok(0) => continue,
+            Ok(_) => hist.push(byte[0] as f32 / 255.0),
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+            Err(_) => break,
+        }
+    }
+}
+
+#[inline]
+fn lerp(range: RangeInclusive<f32>, t: f32) -> f32 {
+    range.start() + (range.end() - range.start()) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReadHistory;
+
+    #[test]
+    fn lerp_maps_t_onto_the_given_range() {
+        assert_eq!(lerp(0.0..=1.0, 0.5), 0.5);
+        assert_eq!(lerp(0.2..=0.8, 0.0), 0.2);
+        assert_eq!(lerp(0.2..=0.8, 1.0), 0.8);
+    }
+
+    #[test]
+    fn stop_does_not_hang_on_a_command_that_goes_quiet() {
+        let hist = Arc::new(ReadHistory::new(4, 0.5, 1.0));
+        let acquisition = Acquisition::start(SourceConfig::Command("sleep 30".into()), hist);
+
+        let start = std::time::Instant::now();
+        acquisition.stop();
+
+        assert!(
+            start.elapsed() < Duration::from_secs(2),
+            "stop() should kill the quiet command instead of hanging on its blocked read"
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use realfft::RealFftPlanner;
+
+/// Magnitude spectrum and dominant frequency estimate for a window of samples.
+pub struct Spectrum {
+    pub magnitudes: Vec<f32>,
+    pub dominant_frequency: f32,
+}
+
+/// Applies a Hann window to reduce the spectral leakage caused by just
+/// slicing out `N` samples (a rectangular window).
+fn hann_window(samples: &[f32]) -> Vec<f32> {
+    let n = samples.len();
+    samples
+        .iter()
+        .enumerate()
+        .map(|(i, &s)| {
+            let w = 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / (n - 1) as f32).cos();
+            s * w
+        })
+        .collect()
+}
+
+/// Runs a real-to-complex FFT over `samples` (expected to already be in
+/// chronological order) and returns the magnitude spectrum plus the
+/// estimated fundamental frequency, excluding the DC bin.
+pub fn analyze(samples: &[f32], sample_rate: f32) -> Spectrum {
+    let mut input = hann_window(samples);
+
+    let mut planner = RealFftPlanner::<f32>::new();
+    let fft = planner.plan_fft_forward(input.len());
+    let mut output = fft.make_output_vec();
+    fft.process(&mut input, &mut output)
+        .expect("hann-windowed buffer should match the planned FFT length");
+
+    let magnitudes: Vec<f32> = output
+        .iter()
+        .map(|bin| (bin.re * bin.re + bin.im * bin.im).sqrt())
+        .collect();
+
+    let n = samples.len() as f32;
+    let peak_bin = magnitudes
+        .iter()
+        .enumerate()
+        .skip(1) // bin 0 is DC, not a frequency component
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(bin, _)| bin)
+        .unwrap_or(0);
+
+    Spectrum {
+        dominant_frequency: peak_bin as f32 * sample_rate / n,
+        magnitudes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovers_a_known_single_tone() {
+        let sample_rate = 1_000.0;
+        let frequency = 50.0; // bin-aligned: falls exactly on an FFT bin
+
+        let samples: Vec<f32> = (0..1_000)
+            .map(|i| (2.0 * std::f32::consts::PI * frequency * i as f32 / sample_rate).sin())
+            .collect();
+
+        let spectrum = analyze(&samples, sample_rate);
+        assert!(
+            (spectrum.dominant_frequency - frequency).abs() < 1.0,
+            "expected to recover {frequency} Hz, got {} Hz",
+            spectrum.dominant_frequency
+        );
+    }
+}
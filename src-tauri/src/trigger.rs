@@ -0,0 +1,162 @@
+/// Slope of the threshold crossing that counts as a trigger event.
+#[derive(Clone, Copy, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Slope {
+    Rising,
+    Falling,
+}
+
+/// How the trigger rearms itself after firing.
+#[derive(Clone, Copy, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// Re-trigger on every matching crossing; free-running.
+    Auto,
+    /// Re-trigger on every matching crossing, same as a repeating capture.
+    Normal,
+    /// Trigger once, then ignore crossings until re-armed.
+    Single,
+}
+
+/// Freezes the displayed trace to a stable reference point instead of
+/// letting it free-run, by watching for a configured threshold crossing
+/// and reporting the buffer offset it landed on.
+pub struct Trigger {
+    threshold: f32,
+    slope: Slope,
+    mode: Mode,
+    last_sample: f32,
+    armed: bool,
+    offset: Option<usize>,
+}
+
+impl Trigger {
+    pub fn new() -> Self {
+        Self {
+            threshold: 0.5,
+            slope: Slope::Rising,
+            mode: Mode::Auto,
+            last_sample: 0.5,
+            armed: true,
+            offset: None,
+        }
+    }
+
+    pub fn set(&mut self, threshold: f32, slope: Slope, mode: Mode) {
+        self.threshold = threshold;
+        self.slope = slope;
+        self.mode = mode;
+        self.armed = true;
+        self.offset = None;
+    }
+
+    /// Re-arms the trigger so the next matching crossing fires again; only
+    /// relevant in `Mode::Single`, which otherwise ignores crossings after
+    /// its first capture.
+    pub fn arm(&mut self) {
+        self.armed = true;
+    }
+
+    /// The buffer index of the most recent trigger event, if any.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Watches for the configured threshold crossing, marking `index` (the
+    /// buffer slot the crossing sample landed in) as the new trigger offset.
+    /// Returns `true` when this crossing just fired a `Mode::Single` trigger,
+    /// telling the caller to stop writing further samples until re-armed.
+    pub fn observe(&mut self, sample: f32, index: usize) -> bool {
+        let crossed = match self.slope {
+            Slope::Rising => self.last_sample < self.threshold && sample >= self.threshold,
+            Slope::Falling => self.last_sample > self.threshold && sample <= self.threshold,
+        };
+        self.last_sample = sample;
+
+        if !crossed || !self.armed {
+            return false;
+        }
+
+        self.offset = Some(index);
+        if matches!(self.mode, Mode::Single) {
+            self.armed = false;
+            return true;
+        }
+
+        false
+    }
+}
+
+impl Default for Trigger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_mode_fires_on_every_crossing_and_never_halts() {
+        let mut trigger = Trigger::new();
+        trigger.set(0.5, Slope::Rising, Mode::Auto);
+
+        assert!(!trigger.observe(0.4, 0));
+        assert!(!trigger.observe(0.6, 1));
+        assert_eq!(trigger.offset(), Some(1));
+
+        // a second crossing re-fires immediately, still without halting
+        assert!(!trigger.observe(0.4, 2));
+        assert!(!trigger.observe(0.6, 3));
+        assert_eq!(trigger.offset(), Some(3));
+    }
+
+    #[test]
+    fn single_mode_fires_once_then_ignores_until_rearmed() {
+        let mut trigger = Trigger::new();
+        trigger.set(0.5, Slope::Rising, Mode::Single);
+
+        assert!(!trigger.observe(0.4, 0));
+        assert!(trigger.observe(0.6, 1));
+        assert_eq!(trigger.offset(), Some(1));
+
+        // disarmed: further crossings are ignored and don't move the offset
+        assert!(!trigger.observe(0.4, 2));
+        assert!(!trigger.observe(0.6, 3));
+        assert_eq!(trigger.offset(), Some(1));
+
+        trigger.arm();
+        assert!(!trigger.observe(0.4, 4));
+        assert!(trigger.observe(0.6, 5));
+        assert_eq!(trigger.offset(), Some(5));
+    }
+
+    #[test]
+    fn falling_slope_ignores_rising_crossings() {
+        let mut trigger = Trigger::new();
+        trigger.set(0.5, Slope::Falling, Mode::Auto);
+
+        assert!(!trigger.observe(0.4, 0));
+        assert!(!trigger.observe(0.6, 1)); // rising: not a match
+        assert_eq!(trigger.offset(), None);
+
+        assert!(!trigger.observe(0.4, 2)); // falling: matches
+        assert_eq!(trigger.offset(), Some(2));
+    }
+
+    #[test]
+    fn set_rearms_and_clears_the_previous_offset() {
+        let mut trigger = Trigger::new();
+        trigger.set(0.5, Slope::Rising, Mode::Single);
+        trigger.observe(0.4, 0);
+        trigger.observe(0.6, 1);
+        assert_eq!(trigger.offset(), Some(1));
+
+        trigger.set(0.5, Slope::Rising, Mode::Single);
+        assert_eq!(trigger.offset(), None);
+        assert!(!trigger.observe(0.4, 2));
+        assert!(trigger.observe(0.6, 3));
+        assert_eq!(trigger.offset(), Some(3));
+    }
+}